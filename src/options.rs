@@ -1,46 +1,399 @@
-use std::{collections::HashMap, path::Path};
+use std::{
+    collections::HashMap,
+    fmt, fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+use bitflags::bitflags;
+use serde::Deserialize;
 
 #[allow(clippy::struct_excessive_bools)]
 pub struct CompilerOptions {
     pub optimization_strategy: OptimizationStrategy,
-    pub enable_bulk_memory: bool,
-    pub enable_sign_extension: bool,
-    pub enable_nontrapping_f2i: bool,
+    pub features: WasmFeatures,
+    pub emit: EmitTargets,
     pub enable_export_memory: bool,
     pub flag_use: HashMap<String, String>,
-    pub trap_on_abort: bool,
+    pub abort: AbortStrategy,
     pub runtime: RuntimeStrategy,
+    /// When set, append `--stats`/`--measure` so `asc` reports timing and size
+    /// statistics. [`Diagnostic::parse_asc_output`] does not turn these
+    /// statistics into diagnostics; it only collects `ERROR`/`WARNING`/`INFO`
+    /// entries.
+    pub measure: bool,
     pub source: String,
 }
 
+/// How a failed `abort()` should be wired up, mirroring the way rustc models
+/// panics as a first-class `PanicStrategy` rather than a boolean.
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AbortStrategy {
+    /// Trap on abort: link the local library and route `abort` to
+    /// `custom_abort`, so the module carries no `env` dependency to handle
+    /// failure. This is the historical default (`--lib . --use abort=custom_abort`).
+    #[default]
+    Trap,
+    /// Leave the default `env.abort` import in place so the host handles
+    /// failures.
+    ImportEnv,
+    /// Route `abort` to a named user function (`--use abort=<name>`).
+    Custom(String),
+}
+
+bitflags! {
+    /// The set of WebAssembly proposals a compilation opts in to.
+    ///
+    /// Modelled on rustc's `SanitizerSet`: one bit per proposal, rather than a
+    /// loose collection of booleans. `asc` enables a handful of proposals by
+    /// default and leaves the rest off; [`CompilerOptions::to_npx_command`]
+    /// uses [`WasmFeatures::DEFAULT_ON`] to decide which proposals to
+    /// `--disable` and which to `--enable`.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct WasmFeatures: u16 {
+        const BULK_MEMORY = 1 << 0;
+        const SIGN_EXTENSION = 1 << 1;
+        const NONTRAPPING_F2I = 1 << 2;
+        const THREADS = 1 << 3;
+        const SIMD = 1 << 4;
+        const REFERENCE_TYPES = 1 << 5;
+        const MULTI_VALUE = 1 << 6;
+        const GC = 1 << 7;
+        const RELAXED_SIMD = 1 << 8;
+        const EXTENDED_CONST = 1 << 9;
+        const TAIL_CALLS = 1 << 10;
+        const MUTABLE_GLOBALS = 1 << 11;
+    }
+}
+
+impl WasmFeatures {
+    /// Proposals `asc` enables unless they are explicitly turned off. These are
+    /// `--disable`d when absent from the set; every other proposal is instead
+    /// `--enable`d when present.
+    pub const DEFAULT_ON: WasmFeatures = WasmFeatures::BULK_MEMORY
+        .union(WasmFeatures::SIGN_EXTENSION)
+        .union(WasmFeatures::NONTRAPPING_F2I);
+
+    /// Each proposal paired with the name `asc` (and the comma-separated
+    /// `from_str`/`Display` form) uses for it, in emission order.
+    const PROPOSALS: &'static [(WasmFeatures, &'static str)] = &[
+        (WasmFeatures::BULK_MEMORY, "bulk-memory"),
+        (WasmFeatures::SIGN_EXTENSION, "sign-extension"),
+        (WasmFeatures::NONTRAPPING_F2I, "nontrapping-f2i"),
+        (WasmFeatures::THREADS, "threads"),
+        (WasmFeatures::SIMD, "simd"),
+        (WasmFeatures::REFERENCE_TYPES, "reference-types"),
+        (WasmFeatures::MULTI_VALUE, "multi-value"),
+        (WasmFeatures::GC, "gc"),
+        (WasmFeatures::RELAXED_SIMD, "relaxed-simd"),
+        (WasmFeatures::EXTENDED_CONST, "extended-const"),
+        (WasmFeatures::TAIL_CALLS, "tail-calls"),
+        (WasmFeatures::MUTABLE_GLOBALS, "mutable-globals"),
+    ];
+}
+
+impl fmt::Display for WasmFeatures {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut first = true;
+        for (flag, name) in WasmFeatures::PROPOSALS {
+            if self.contains(*flag) {
+                if !first {
+                    f.write_str(",")?;
+                }
+                f.write_str(name)?;
+                first = false;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Error returned when a feature name in a comma-separated list is unknown.
+#[derive(Debug, PartialEq, Eq)]
+pub struct UnknownFeature(pub String);
+
+impl fmt::Display for UnknownFeature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown WebAssembly feature: {}", self.0)
+    }
+}
+
+impl std::error::Error for UnknownFeature {}
+
+impl FromStr for WasmFeatures {
+    type Err = UnknownFeature;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut features = WasmFeatures::empty();
+        for name in s.split(',').map(str::trim).filter(|name| !name.is_empty()) {
+            let (flag, _) = WasmFeatures::PROPOSALS
+                .iter()
+                .find(|(_, proposal)| *proposal == name)
+                .ok_or_else(|| UnknownFeature(name.to_string()))?;
+            features |= *flag;
+        }
+        Ok(features)
+    }
+}
+
+impl<'de> Deserialize<'de> for WasmFeatures {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let list = String::deserialize(deserializer)?;
+        list.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+bitflags! {
+    /// The set of artifacts a single `asc` invocation should produce.
+    ///
+    /// Like rustc's multiple `--emit` output types, one compile can emit more
+    /// than the wasm binary: the text format, a source map, TypeScript
+    /// declarations and ESM bindings. The concrete output paths are all derived
+    /// from the base output path passed to [`CompilerOptions::to_npx_command`].
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct EmitTargets: u8 {
+        /// The wasm binary, emitted with `-o`.
+        const WASM = 1 << 0;
+        /// The textual `wat` format, emitted with `--textFile`.
+        const TEXT = 1 << 1;
+        /// A source map alongside the binary, emitted with `--sourceMap`.
+        const SOURCE_MAP = 1 << 2;
+        /// TypeScript declarations, emitted with `--tsd`.
+        const TYPE_DECLARATIONS = 1 << 3;
+        /// JavaScript/ESM bindings, emitted with `--bindings esm`.
+        const BINDINGS = 1 << 4;
+    }
+}
+
+impl Default for EmitTargets {
+    fn default() -> Self {
+        // Match the historical behavior: a single wasm binary.
+        EmitTargets::WASM
+    }
+}
+
+impl<'de> Deserialize<'de> for EmitTargets {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let mut targets = EmitTargets::empty();
+        for name in Vec::<String>::deserialize(deserializer)? {
+            targets |= match name.as_str() {
+                "wasm" => EmitTargets::WASM,
+                "text" => EmitTargets::TEXT,
+                "sourceMap" => EmitTargets::SOURCE_MAP,
+                "tsd" => EmitTargets::TYPE_DECLARATIONS,
+                "bindings" => EmitTargets::BINDINGS,
+                other => {
+                    return Err(serde::de::Error::custom(format!(
+                        "unknown emit target: {other}"
+                    )));
+                }
+            };
+        }
+        Ok(targets)
+    }
+}
+
 impl CompilerOptions {
     pub fn default_for(library_source: impl Into<String>) -> Self {
         Self {
             source: library_source.into(),
             // By default, trap on abort.
             // This makes that the module has no 'env' dependency to handle failure.
-            trap_on_abort: true,
+            abort: AbortStrategy::Trap,
             // Other options are set to default
-            enable_bulk_memory: false,
-            enable_nontrapping_f2i: false,
+            features: WasmFeatures::empty(),
+            emit: EmitTargets::default(),
             enable_export_memory: false,
-            enable_sign_extension: false,
             flag_use: HashMap::default(),
             optimization_strategy: OptimizationStrategy::default(),
             runtime: RuntimeStrategy::default(),
+            measure: false,
+        }
+    }
+
+    /// Reject option combinations `asc` can't honor before a command is ever
+    /// spawned. Modelled on the assertion-style incompatibility checks build
+    /// configs use (rustc's `check_ci_llvm!`): every `require!` records a
+    /// conflict rather than aborting, so the caller gets the complete list at
+    /// once.
+    pub fn validate(&self) -> Result<(), Vec<OptionConflict>> {
+        let mut conflicts = Vec::new();
+
+        macro_rules! require {
+            ($cond:expr, $conflict:expr) => {
+                if !($cond) {
+                    conflicts.push($conflict);
+                }
+            };
+        }
+
+        if let AbortStrategy::Custom(function) = &self.abort {
+            require!(
+                !function.trim().is_empty(),
+                OptionConflict::EmptyCustomAbort
+            );
+        }
+
+        // The stub runtime omits the allocator and bookkeeping the GC proposal
+        // relies on, so the pair can't be honored.
+        require!(
+            !(matches!(self.runtime, RuntimeStrategy::Stub)
+                && self.features.contains(WasmFeatures::GC)),
+            OptionConflict::IncompatibleRuntimeFeature {
+                runtime: "stub",
+                feature: "gc",
+            }
+        );
+
+        // A compilation that emits nothing is almost certainly a mistake.
+        require!(!self.emit.is_empty(), OptionConflict::NoEmitTargets);
+
+        if conflicts.is_empty() {
+            Ok(())
+        } else {
+            Err(conflicts)
         }
     }
+
+    /// Load the full option set from a `config.toml`-style file.
+    ///
+    /// This lets projects check their compiler settings into version control
+    /// and reproduce a build without recompiling the Rust driver. Any key left
+    /// out of the file falls back to the same value [`Self::default_for`] would
+    /// pick.
+    pub fn from_config_file(path: &Path) -> Result<Self, ConfigError> {
+        let contents = fs::read_to_string(path)?;
+        Self::from_toml_str(&contents)
+    }
+
+    /// Deserialize the option set from an in-memory TOML document.
+    pub fn from_toml_str(toml: &str) -> Result<Self, ConfigError> {
+        let config: CompilerOptionsConfig = toml::from_str(toml)?;
+        Ok(config.into())
+    }
+}
+
+/// The decoded shape of a compiler `config.toml`, mirroring the approach
+/// external build systems take: a plain `Deserialize` struct that is filled
+/// from TOML and then folded into [`CompilerOptions`]. Unset keys default to
+/// the values produced by [`CompilerOptions::default_for`].
+#[derive(Deserialize)]
+#[serde(default)]
+struct CompilerOptionsConfig {
+    source: String,
+    optimization: OptimizationStrategy,
+    runtime: RuntimeStrategy,
+    abort: AbortStrategy,
+    features: WasmFeatures,
+    emit: EmitTargets,
+    measure: bool,
+    enable_export_memory: bool,
+    #[serde(rename = "use")]
+    flag_use: HashMap<String, String>,
+}
+
+impl Default for CompilerOptionsConfig {
+    fn default() -> Self {
+        let CompilerOptions {
+            optimization_strategy,
+            features,
+            emit,
+            enable_export_memory,
+            flag_use,
+            abort,
+            runtime,
+            measure,
+            source,
+        } = CompilerOptions::default_for(String::new());
+        Self {
+            source,
+            optimization: optimization_strategy,
+            runtime,
+            abort,
+            features,
+            emit,
+            measure,
+            enable_export_memory,
+            flag_use,
+        }
+    }
+}
+
+impl From<CompilerOptionsConfig> for CompilerOptions {
+    fn from(config: CompilerOptionsConfig) -> Self {
+        Self {
+            source: config.source,
+            optimization_strategy: config.optimization,
+            runtime: config.runtime,
+            abort: config.abort,
+            features: config.features,
+            emit: config.emit,
+            measure: config.measure,
+            enable_export_memory: config.enable_export_memory,
+            flag_use: config.flag_use,
+        }
+    }
+}
+
+/// Error returned while loading [`CompilerOptions`] from a config file.
+#[derive(Debug)]
+pub enum ConfigError {
+    /// The config file could not be read from disk.
+    Io(std::io::Error),
+    /// The config file was not valid TOML, or did not match the schema.
+    Parse(toml::de::Error),
+}
+
+impl std::fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigError::Io(error) => write!(f, "could not read config file: {error}"),
+            ConfigError::Parse(error) => write!(f, "could not parse config file: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl From<std::io::Error> for ConfigError {
+    fn from(error: std::io::Error) -> Self {
+        ConfigError::Io(error)
+    }
 }
 
-#[derive(Default)]
+impl From<toml::de::Error> for ConfigError {
+    fn from(error: toml::de::Error) -> Self {
+        ConfigError::Parse(error)
+    }
+}
+
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum OptimizationStrategy {
+    O0,
     O1,
     O2,
     #[default]
     O3,
+    /// Optimize for code size (`-Os`).
+    Os,
+    /// Optimize aggressively for code size (`-Oz`).
+    Oz,
+    /// Escape hatch mirroring `asc`'s two independent knobs: speed
+    /// (`--optimizeLevel 0..3`) and size (`--shrinkLevel 0..2`). Use this when
+    /// a preset's fixed pairing isn't the trade-off you want.
+    Explicit { optimize: u8, shrink: u8 },
 }
 
-#[derive(Default)]
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum RuntimeStrategy {
     #[default]
     Incremental,
@@ -49,24 +402,83 @@ pub enum RuntimeStrategy {
 }
 
 impl CompilerOptions {
+    fn text_path(output_path: &Path) -> PathBuf {
+        output_path.with_extension("wat")
+    }
+
+    fn source_map_path(output_path: &Path) -> PathBuf {
+        let mut path = output_path.as_os_str().to_owned();
+        path.push(".map");
+        PathBuf::from(path)
+    }
+
+    fn type_declarations_path(output_path: &Path) -> PathBuf {
+        output_path.with_extension("d.ts")
+    }
+
+    fn bindings_path(output_path: &Path) -> PathBuf {
+        output_path.with_extension("js")
+    }
+
+    /// The files produced by a compilation with these options, so downstream
+    /// tooling can locate every artifact without re-deriving the paths. The
+    /// paths mirror exactly the outputs [`Self::to_npx_command`] asks `asc` to
+    /// write.
+    pub fn produced_files(&self, output_path: &Path) -> Vec<PathBuf> {
+        let mut files = Vec::new();
+        if self.emit.contains(EmitTargets::WASM) {
+            files.push(output_path.to_path_buf());
+        }
+        if self.emit.contains(EmitTargets::TEXT) {
+            files.push(Self::text_path(output_path));
+        }
+        if self.emit.contains(EmitTargets::SOURCE_MAP) {
+            files.push(Self::source_map_path(output_path));
+        }
+        if self.emit.contains(EmitTargets::TYPE_DECLARATIONS) {
+            files.push(Self::type_declarations_path(output_path));
+        }
+        if self.emit.contains(EmitTargets::BINDINGS) {
+            files.push(Self::bindings_path(output_path));
+        }
+        files
+    }
+
     pub(crate) fn to_npx_command(&self, source_path: &Path, output_path: &Path) -> String {
-        let flag_bulk_memory = if self.enable_bulk_memory {
-            ""
-        } else {
-            "--disable bulk-memory "
-        };
+        let mut flag_features = String::new();
+        for (flag, name) in WasmFeatures::PROPOSALS {
+            let enabled = self.features.contains(*flag);
+            if WasmFeatures::DEFAULT_ON.contains(*flag) {
+                if !enabled {
+                    flag_features.push_str(&format!("--disable {name} "));
+                }
+            } else if enabled {
+                flag_features.push_str(&format!("--enable {name} "));
+            }
+        }
 
-        let flag_sign_extension = if self.enable_sign_extension {
-            ""
+        let flag_output = if self.emit.contains(EmitTargets::WASM) {
+            format!("-o {output_path:?} ")
         } else {
-            "--disable sign-extension "
+            String::new()
         };
 
-        let flag_non_trapping_f2i = if self.enable_nontrapping_f2i {
-            ""
-        } else {
-            "--disable nontrapping-f2i "
-        };
+        let mut flag_emit = String::new();
+        if self.emit.contains(EmitTargets::TEXT) {
+            flag_emit.push_str(&format!("--textFile {:?} ", Self::text_path(output_path)));
+        }
+        if self.emit.contains(EmitTargets::SOURCE_MAP) {
+            flag_emit.push_str("--sourceMap ");
+        }
+        if self.emit.contains(EmitTargets::TYPE_DECLARATIONS) {
+            flag_emit.push_str(&format!(
+                "--tsd {:?} ",
+                Self::type_declarations_path(output_path)
+            ));
+        }
+        if self.emit.contains(EmitTargets::BINDINGS) {
+            flag_emit.push_str("--bindings esm ");
+        }
 
         let flag_export_memory = if self.enable_export_memory {
             ""
@@ -80,17 +492,43 @@ impl CompilerOptions {
             RuntimeStrategy::Stub => "--runtime stub ",
         };
 
-        let flag_optimization = match self.optimization_strategy {
-            OptimizationStrategy::O1 => "-O1 ",
-            OptimizationStrategy::O2 => "-O2 ",
-            OptimizationStrategy::O3 => "-O3 ",
+        let flag_optimization = match &self.optimization_strategy {
+            OptimizationStrategy::O0 => "-O0 ".to_string(),
+            OptimizationStrategy::O1 => "-O1 ".to_string(),
+            OptimizationStrategy::O2 => "-O2 ".to_string(),
+            OptimizationStrategy::O3 => "-O3 ".to_string(),
+            OptimizationStrategy::Os => "-Os ".to_string(),
+            OptimizationStrategy::Oz => "-Oz ".to_string(),
+            OptimizationStrategy::Explicit { optimize, shrink } => {
+                format!("--optimizeLevel {optimize} --shrinkLevel {shrink} ")
+            }
         };
 
-        let flag_use = match (self.flag_use.is_empty(), self.trap_on_abort) {
-            // No custom flags, no trap on abort
-            (true, false) => String::new(),
-            // Custom flags but no trap on abort
-            (false, false) => format!(
+        // The abort strategy decides whether a local library is linked and
+        // which `abort=<...>` entry (if any) is merged into the `--use` list.
+        let abort_injection = match &self.abort {
+            AbortStrategy::Trap => Some("custom_abort"),
+            AbortStrategy::Custom(function) => Some(function.as_str()),
+            AbortStrategy::ImportEnv => None,
+        };
+
+        let flag_use = match abort_injection {
+            // An injecting strategy links the local library and folds its
+            // `abort=<...>` entry in with any user-provided `--use` flags.
+            Some(abort) => format!(
+                "--lib . --use {} ",
+                self.flag_use
+                    .iter()
+                    .map(|(k, v)| (k.as_str(), v.as_str()))
+                    .chain(vec![("abort", abort)])
+                    .map(|(key, value)| format!("{key}={value}"))
+                    .collect::<Vec<String>>()
+                    .join(" ")
+            ),
+            // `ImportEnv` leaves `env.abort` alone and only forwards the user's
+            // own `--use` flags, if any.
+            None if self.flag_use.is_empty() => String::new(),
+            None => format!(
                 "--use {} ",
                 self.flag_use
                     .iter()
@@ -98,19 +536,6 @@ impl CompilerOptions {
                     .collect::<Vec<String>>()
                     .join(" ")
             ),
-            // Trap on abort
-            (true, true) | (false, true) => {
-                format!(
-                    "--lib . --use {} ",
-                    self.flag_use
-                        .iter()
-                        .map(|(k, v)| (k.as_str(), v.as_str()))
-                        .chain(vec![("abort", "custom_abort")]) // include trap
-                        .map(|(key, value)| format!("{key}={value}"))
-                        .collect::<Vec<String>>()
-                        .join(" ")
-                )
-            }
         };
 
         cfg_if::cfg_if! {
@@ -128,30 +553,133 @@ impl CompilerOptions {
         format!(
             concat!(
                 // Pass input file & output file to command
-                "{compiler_runtime_command} {source_path:?} -o {output_path:?} ",
+                "{compiler_runtime_command} {source_path:?} {flag_output}{flag_emit}",
                 // Pas additional options to command
                 "{flag_optimization}",
-                "{flag_bulk_memory}",
-                "{flag_sign_extension}",
-                "{flag_non_trapping_f2i}",
+                "{flag_features}",
                 "{flag_runtime}",
                 "{flag_export_memory}",
                 "{flag_use}",
+                "{flag_measure}",
             ),
             compiler_runtime_command = runtime(),
             source_path = &source_path,
-            output_path = &output_path,
-            flag_bulk_memory = flag_bulk_memory,
-            flag_sign_extension = flag_sign_extension,
-            flag_non_trapping_f2i = flag_non_trapping_f2i,
+            flag_output = flag_output,
+            flag_emit = flag_emit,
+            flag_features = flag_features,
             flag_runtime = flag_runtime,
             flag_export_memory = flag_export_memory,
             flag_optimization = flag_optimization,
             flag_use = flag_use,
+            flag_measure = if self.measure { "--stats --measure " } else { "" },
         )
     }
 }
 
+/// An incompatible combination of options rejected by
+/// [`CompilerOptions::validate`].
+#[derive(Debug, PartialEq, Eq)]
+pub enum OptionConflict {
+    /// [`AbortStrategy::Custom`] was given an empty function name.
+    EmptyCustomAbort,
+    /// A runtime and a feature that `asc` can't honor together.
+    IncompatibleRuntimeFeature {
+        runtime: &'static str,
+        feature: &'static str,
+    },
+    /// The emit set was empty, so the compilation would produce nothing.
+    NoEmitTargets,
+}
+
+impl fmt::Display for OptionConflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OptionConflict::EmptyCustomAbort => {
+                f.write_str("custom abort strategy requires a non-empty function name")
+            }
+            OptionConflict::IncompatibleRuntimeFeature { runtime, feature } => write!(
+                f,
+                "the {runtime} runtime is incompatible with the {feature} feature"
+            ),
+            OptionConflict::NoEmitTargets => {
+                f.write_str("at least one emit target must be selected")
+            }
+        }
+    }
+}
+
+/// The severity `asc` attaches to a diagnostic line.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+    Info,
+}
+
+/// A single machine-readable diagnostic parsed from `asc`'s output, so callers
+/// get structured errors instead of raw stderr text.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    pub file: Option<String>,
+    pub line: Option<u32>,
+}
+
+impl Diagnostic {
+    /// Parse `asc`'s human-readable output into structured diagnostics. Each
+    /// `ERROR`/`WARNING`/`INFO` line opens a diagnostic; a following
+    /// `in <file>(<line>,<col>)` location line, if present, fills in the source
+    /// position.
+    pub fn parse_asc_output(output: &str) -> Vec<Diagnostic> {
+        let mut diagnostics = Vec::new();
+        let mut current: Option<Diagnostic> = None;
+
+        for line in output.lines() {
+            let trimmed = line.trim();
+            let severity = if let Some(rest) = trimmed.strip_prefix("ERROR") {
+                Some((Severity::Error, rest))
+            } else if let Some(rest) = trimmed.strip_prefix("WARNING") {
+                Some((Severity::Warning, rest))
+            } else if let Some(rest) = trimmed.strip_prefix("INFO") {
+                Some((Severity::Info, rest))
+            } else {
+                None
+            };
+
+            if let Some((severity, rest)) = severity {
+                if let Some(diagnostic) = current.take() {
+                    diagnostics.push(diagnostic);
+                }
+                current = Some(Diagnostic {
+                    severity,
+                    message: rest.trim().to_string(),
+                    file: None,
+                    line: None,
+                });
+            } else if let Some(diagnostic) = current.as_mut() {
+                if let Some((file, line)) = parse_location(trimmed) {
+                    diagnostic.file = Some(file);
+                    diagnostic.line = Some(line);
+                }
+            }
+        }
+
+        diagnostics.extend(current);
+        diagnostics
+    }
+}
+
+/// Extract `(file, line)` from an `in <file>(<line>,<col>)` location marker.
+fn parse_location(line: &str) -> Option<(String, u32)> {
+    let rest = &line[line.find("in ")? + 3..];
+    let paren = rest.find('(')?;
+    let file = rest[..paren].trim().to_string();
+    let inside = &rest[paren + 1..rest.find(')')?];
+    let line_no = inside.split(',').next()?.trim().parse().ok()?;
+    Some((file, line_no))
+}
+
 #[cfg(test)]
 mod tests {
     use std::path::PathBuf;
@@ -183,13 +711,15 @@ mod tests {
     fn test_to_npx() {
         let mut options = CompilerOptions {
             optimization_strategy: OptimizationStrategy::O1,
-            enable_bulk_memory: true,
-            enable_sign_extension: true,
-            enable_nontrapping_f2i: true,
+            features: WasmFeatures::BULK_MEMORY
+                | WasmFeatures::SIGN_EXTENSION
+                | WasmFeatures::NONTRAPPING_F2I,
+            emit: EmitTargets::WASM,
             enable_export_memory: true,
             flag_use: HashMap::new(),
-            trap_on_abort: true,
+            abort: AbortStrategy::Trap,
             runtime: super::RuntimeStrategy::Incremental,
+            measure: false,
             source: "".to_string(),
         };
 
@@ -208,13 +738,13 @@ mod tests {
 
         options = CompilerOptions {
             optimization_strategy: OptimizationStrategy::O2,
-            enable_bulk_memory: false,
-            enable_sign_extension: false,
-            enable_nontrapping_f2i: false,
+            features: WasmFeatures::empty(),
+            emit: EmitTargets::WASM,
             enable_export_memory: false,
             flag_use: HashMap::new(),
-            trap_on_abort: false,
+            abort: AbortStrategy::ImportEnv,
             runtime: super::RuntimeStrategy::Incremental,
+            measure: false,
             source: "".to_string(),
         };
 
@@ -232,4 +762,207 @@ mod tests {
                 )),
         );
     }
+
+    #[test]
+    fn test_from_toml_str() {
+        let options = CompilerOptions::from_toml_str(concat!(
+            "source = \"lib.ts\"\n",
+            "optimization = \"o1\"\n",
+            "runtime = \"minimal\"\n",
+            "abort = \"importenv\"\n",
+            "features = \"bulk-memory,simd\"\n",
+            "[use]\n",
+            "trace = \"my_trace\"\n",
+        ))
+        .expect("valid config should parse");
+
+        assert_eq!(options.source, "lib.ts");
+        assert!(matches!(
+            options.optimization_strategy,
+            OptimizationStrategy::O1
+        ));
+        assert!(matches!(options.runtime, RuntimeStrategy::Minimal));
+        assert!(matches!(options.abort, AbortStrategy::ImportEnv));
+        assert_eq!(
+            options.features,
+            WasmFeatures::BULK_MEMORY | WasmFeatures::SIMD
+        );
+        assert_eq!(options.flag_use.get("trace").map(String::as_str), Some("my_trace"));
+    }
+
+    #[test]
+    fn test_from_toml_str_defaults() {
+        // An empty document should reproduce `default_for("")`.
+        let options = CompilerOptions::from_toml_str("").expect("empty config should parse");
+
+        assert!(matches!(options.abort, AbortStrategy::Trap));
+        assert_eq!(options.features, WasmFeatures::empty());
+        assert!(matches!(
+            options.optimization_strategy,
+            OptimizationStrategy::O3
+        ));
+        assert!(matches!(options.runtime, RuntimeStrategy::Incremental));
+    }
+
+    #[test]
+    fn test_validate_conflicts() {
+        // The default options are a valid combination.
+        assert!(CompilerOptions::default_for("").validate().is_ok());
+
+        let mut options = CompilerOptions::default_for("");
+        options.abort = AbortStrategy::Custom("  ".to_string());
+        options.runtime = RuntimeStrategy::Stub;
+        options.features = WasmFeatures::GC;
+        options.emit = EmitTargets::empty();
+
+        let conflicts = options.validate().expect_err("conflicts expected");
+        assert!(conflicts.contains(&OptionConflict::EmptyCustomAbort));
+        assert!(conflicts.contains(&OptionConflict::IncompatibleRuntimeFeature {
+            runtime: "stub",
+            feature: "gc",
+        }));
+        assert!(conflicts.contains(&OptionConflict::NoEmitTargets));
+    }
+
+    #[test]
+    fn test_measure_flag() {
+        let mut options = CompilerOptions::default_for("");
+        options.measure = true;
+        assert!(
+            options
+                .to_npx_command(&PathBuf::from("s"), &PathBuf::from("o"))
+                .contains("--stats --measure ")
+        );
+    }
+
+    #[test]
+    fn test_parse_asc_output() {
+        let output = concat!(
+            "ERROR TS2304: Cannot find name 'foo'.\n",
+            "   └─ in assembly/index.ts(3,3)\n",
+            "WARNING TS6133: 'bar' is declared but never used.\n",
+        );
+
+        let diagnostics = Diagnostic::parse_asc_output(output);
+        assert_eq!(
+            diagnostics,
+            vec![
+                Diagnostic {
+                    severity: Severity::Error,
+                    message: "TS2304: Cannot find name 'foo'.".to_string(),
+                    file: Some("assembly/index.ts".to_string()),
+                    line: Some(3),
+                },
+                Diagnostic {
+                    severity: Severity::Warning,
+                    message: "TS6133: 'bar' is declared but never used.".to_string(),
+                    file: None,
+                    line: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_abort_strategies() {
+        let source_path = PathBuf::from("s");
+        let output_path = PathBuf::from("o");
+
+        let mut options = CompilerOptions::default_for("");
+        // `ImportEnv` neither links the library nor injects an abort entry.
+        options.abort = AbortStrategy::ImportEnv;
+        let command = options.to_npx_command(&source_path, &output_path);
+        assert!(!command.contains("--lib ."));
+        assert!(!command.contains("abort="));
+
+        // `Custom` links the library and routes abort to the named function,
+        // merged with the user's own `--use` flags.
+        options.abort = AbortStrategy::Custom("my_abort".to_string());
+        options
+            .flag_use
+            .insert("trace".to_string(), "my_trace".to_string());
+        let command = options.to_npx_command(&source_path, &output_path);
+        assert!(command.contains("--lib . --use "));
+        assert!(command.contains("abort=my_abort"));
+        assert!(command.contains("trace=my_trace"));
+    }
+
+    #[test]
+    fn test_emit_targets_command_and_files() {
+        let mut options = CompilerOptions::default_for("");
+        options.emit = EmitTargets::WASM
+            | EmitTargets::TEXT
+            | EmitTargets::SOURCE_MAP
+            | EmitTargets::TYPE_DECLARATIONS
+            | EmitTargets::BINDINGS;
+
+        let output_path = PathBuf::from("dist").join("module.wasm");
+        let command = options.to_npx_command(&PathBuf::from("s"), &output_path);
+
+        assert!(command.contains("-o \"dist/module.wasm\" "));
+        assert!(command.contains("--textFile \"dist/module.wat\" "));
+        assert!(command.contains("--sourceMap "));
+        assert!(command.contains("--tsd \"dist/module.d.ts\" "));
+        assert!(command.contains("--bindings esm "));
+
+        assert_eq!(
+            options.produced_files(&output_path),
+            vec![
+                PathBuf::from("dist/module.wasm"),
+                PathBuf::from("dist/module.wat"),
+                PathBuf::from("dist/module.wasm.map"),
+                PathBuf::from("dist/module.d.ts"),
+                PathBuf::from("dist/module.js"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_optimization_presets_and_explicit() {
+        let source_path = PathBuf::from("s");
+        let output_path = PathBuf::from("o");
+
+        let mut options = CompilerOptions::default_for("");
+        options.optimization_strategy = OptimizationStrategy::Oz;
+        assert!(
+            options
+                .to_npx_command(&source_path, &output_path)
+                .contains("-Oz ")
+        );
+
+        options.optimization_strategy = OptimizationStrategy::Explicit {
+            optimize: 2,
+            shrink: 1,
+        };
+        assert!(
+            options
+                .to_npx_command(&source_path, &output_path)
+                .contains("--optimizeLevel 2 --shrinkLevel 1 ")
+        );
+    }
+
+    #[test]
+    fn test_feature_flags_in_command() {
+        let mut options = CompilerOptions::default_for("");
+        // Turn on an off-by-default proposal and one of the default-on ones.
+        options.features = WasmFeatures::SIMD | WasmFeatures::BULK_MEMORY;
+
+        let command = options.to_npx_command(&PathBuf::from("s"), &PathBuf::from("o"));
+
+        // Default-on proposal present → no `--disable`; off-by-default present → `--enable`.
+        assert!(!command.contains("--disable bulk-memory"));
+        assert!(command.contains("--disable sign-extension "));
+        assert!(command.contains("--enable simd "));
+    }
+
+    #[test]
+    fn test_wasm_features_parse_roundtrip() {
+        let parsed: WasmFeatures = "simd, tail-calls".parse().expect("known features");
+        assert_eq!(parsed, WasmFeatures::SIMD | WasmFeatures::TAIL_CALLS);
+        assert_eq!(parsed.to_string(), "simd,tail-calls");
+        assert_eq!(
+            "bogus".parse::<WasmFeatures>(),
+            Err(UnknownFeature("bogus".to_string()))
+        );
+    }
 }